@@ -0,0 +1,117 @@
+//! TOML configuration: `$XDG_CONFIG_HOME/lancelot/config.toml`.
+//!
+//! Lets users customize lancelot without recompiling — icon theme, extra
+//! desktop-file search directories, window size, the terminal emulator used
+//! for `Terminal=true` entries, how many results are shown, and basic visual
+//! overrides. Sane defaults apply when the file is absent or unreadable.
+
+use serde::Deserialize;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub icon_theme: String,
+    pub search_dirs: Vec<String>,
+    pub script_plugins: Vec<String>,
+    pub enable_path_source: bool,
+    pub window_size: [f32; 2],
+    pub terminal: Option<String>,
+    pub result_limit: usize,
+    pub accent_color: [u8; 3],
+    pub row_height: f32,
+    pub icon_size: u32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            icon_theme: "hicolor".to_string(),
+            search_dirs: Vec::new(),
+            script_plugins: Vec::new(),
+            enable_path_source: true,
+            window_size: [400.0, 300.0],
+            terminal: None,
+            result_limit: 50,
+            accent_color: [66, 135, 245],
+            row_height: 32.0,
+            icon_size: 24,
+        }
+    }
+}
+
+impl Config {
+    /// Loads `config.toml`, falling back to `Config::default()` if it's
+    /// absent or fails to parse.
+    pub fn load() -> Self {
+        let Ok(contents) = std::fs::read_to_string(config_path()) else {
+            return Config::default();
+        };
+        toml::from_str(&contents).unwrap_or_default()
+    }
+
+    /// `search_dirs`, each with `~` and `$VAR`/`${VAR}` expanded.
+    pub fn expanded_search_dirs(&self) -> Vec<PathBuf> {
+        self.search_dirs.iter().map(|dir| expand_path(dir)).collect()
+    }
+
+    /// `script_plugins`, each with `~` and `$VAR`/`${VAR}` expanded, ready to
+    /// hand to `sources::enabled_sources`.
+    pub fn expanded_script_plugins(&self) -> Vec<PathBuf> {
+        self.script_plugins.iter().map(|dir| expand_path(dir)).collect()
+    }
+}
+
+fn expand_path(raw: &str) -> PathBuf {
+    let raw = if let Some(rest) = raw.strip_prefix("~/") {
+        match dirs::home_dir() {
+            Some(home) => return PathBuf::from(expand_env(&home.join(rest).to_string_lossy())),
+            None => raw,
+        }
+    } else {
+        raw
+    };
+    PathBuf::from(expand_env(raw))
+}
+
+/// Expands `$VAR` and `${VAR}` references using the process environment.
+fn expand_env(raw: &str) -> String {
+    let mut result = String::new();
+    let mut chars = raw.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            result.push(c);
+            continue;
+        }
+        let braced = chars.peek() == Some(&'{');
+        if braced {
+            chars.next();
+        }
+        let mut name = String::new();
+        while let Some(&next) = chars.peek() {
+            if braced {
+                if next == '}' {
+                    chars.next();
+                    break;
+                }
+            } else if !(next.is_alphanumeric() || next == '_') {
+                break;
+            }
+            name.push(next);
+            chars.next();
+        }
+        match std::env::var(&name) {
+            Ok(value) => result.push_str(&value),
+            Err(_) => result.push_str(&name),
+        }
+    }
+    result
+}
+
+fn config_path() -> PathBuf {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".config")))
+        .unwrap_or_else(|| PathBuf::from(".config"));
+    config_dir.join("lancelot").join("config.toml")
+}
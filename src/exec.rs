@@ -0,0 +1,214 @@
+//! freedesktop `Exec=` parsing: quote-aware tokenizing, field-code expansion,
+//! and `Terminal=true` wrapping.
+//!
+//! Replaces the old approach of just dropping any token that *contains* a
+//! `%`, which broke quoting and ignored `Terminal=`.
+
+use std::env;
+
+/// Context needed to expand the field codes that don't come from a file/URL
+/// argument (we never have one — lancelot launches apps, it doesn't open
+/// files).
+pub struct ExecContext<'a> {
+    pub icon: Option<&'a str>,
+    pub name: &'a str,
+}
+
+/// Splits an `Exec=` value into argv tokens, honoring `"`-quoting and
+/// backslash escapes as described by the Desktop Entry Specification.
+pub fn tokenize(exec: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_token = false;
+    let mut chars = exec.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if in_quotes => match chars.peek() {
+                Some(&next) if matches!(next, '"' | '\\' | '$' | '`') => {
+                    current.push(next);
+                    chars.next();
+                }
+                _ => current.push(c),
+            },
+            '"' => {
+                in_quotes = !in_quotes;
+                has_token = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_token {
+                    tokens.push(std::mem::take(&mut current));
+                    has_token = false;
+                }
+            }
+            _ => {
+                current.push(c);
+                has_token = true;
+            }
+        }
+    }
+    if has_token {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// Expands field codes in already-tokenized argv. `%i` becomes `--icon
+/// <icon>` (dropped entirely if there is no icon), `%c` becomes the
+/// translated name, `%%` is a literal `%`, and the file/url codes
+/// (`%f %F %u %U %d %D %n %N %k`) are dropped since we never supply one.
+pub fn expand_field_codes(tokens: &[String], ctx: &ExecContext) -> Vec<String> {
+    let mut out = Vec::new();
+    for token in tokens {
+        match token.as_str() {
+            "%f" | "%F" | "%u" | "%U" | "%d" | "%D" | "%n" | "%N" | "%k" => continue,
+            "%i" => {
+                if let Some(icon) = ctx.icon {
+                    out.push("--icon".to_string());
+                    out.push(icon.to_string());
+                }
+            }
+            "%c" => out.push(ctx.name.to_string()),
+            _ => out.push(token.replace("%%", "%")),
+        }
+    }
+    out
+}
+
+/// The terminal emulator to wrap `Terminal=true` entries in: `terminal`
+/// (the user's configured override) if set, else `$TERMINAL`, else the
+/// `x-terminal-emulator` alternatives-system fallback.
+pub fn terminal_emulator(terminal: Option<&str>) -> String {
+    terminal
+        .map(str::to_string)
+        .or_else(|| env::var("TERMINAL").ok())
+        .unwrap_or_else(|| "x-terminal-emulator".to_string())
+}
+
+/// Tokenizes `exec`, expands its field codes, and, if `wrap_in_terminal` is
+/// set, wraps the result so it runs inside the user's terminal emulator.
+pub fn build_argv(
+    exec: &str,
+    ctx: &ExecContext,
+    wrap_in_terminal: bool,
+    terminal: Option<&str>,
+) -> Vec<String> {
+    let argv = expand_field_codes(&tokenize(exec), ctx);
+    if wrap_in_terminal {
+        let mut wrapped = vec![terminal_emulator(terminal), "-e".to_string()];
+        wrapped.extend(argv);
+        wrapped
+    } else {
+        argv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenize_splits_on_whitespace() {
+        assert_eq!(
+            tokenize("firefox --new-window"),
+            vec!["firefox", "--new-window"]
+        );
+    }
+
+    #[test]
+    fn tokenize_keeps_quoted_argument_together() {
+        assert_eq!(
+            tokenize(r#"sh -c "echo hello world""#),
+            vec!["sh", "-c", "echo hello world"]
+        );
+    }
+
+    #[test]
+    fn tokenize_honors_escaped_quote_inside_quotes() {
+        assert_eq!(
+            tokenize(r#"echo "say \"hi\"""#),
+            vec!["echo", "say \"hi\""]
+        );
+    }
+
+    #[test]
+    fn expand_field_codes_drops_file_and_url_codes() {
+        let ctx = ExecContext {
+            icon: None,
+            name: "App",
+        };
+        let tokens = vec!["app".to_string(), "%f".to_string(), "%U".to_string()];
+        assert_eq!(expand_field_codes(&tokens, &ctx), vec!["app".to_string()]);
+    }
+
+    #[test]
+    fn expand_field_codes_expands_icon_flag() {
+        let ctx = ExecContext {
+            icon: Some("app-icon"),
+            name: "App",
+        };
+        let tokens = vec!["app".to_string(), "%i".to_string()];
+        assert_eq!(
+            expand_field_codes(&tokens, &ctx),
+            vec!["app".to_string(), "--icon".to_string(), "app-icon".to_string()]
+        );
+    }
+
+    #[test]
+    fn expand_field_codes_drops_icon_flag_when_no_icon() {
+        let ctx = ExecContext {
+            icon: None,
+            name: "App",
+        };
+        let tokens = vec!["app".to_string(), "%i".to_string()];
+        assert_eq!(expand_field_codes(&tokens, &ctx), vec!["app".to_string()]);
+    }
+
+    #[test]
+    fn expand_field_codes_expands_name() {
+        let ctx = ExecContext {
+            icon: None,
+            name: "My App",
+        };
+        let tokens = vec!["app".to_string(), "%c".to_string()];
+        assert_eq!(
+            expand_field_codes(&tokens, &ctx),
+            vec!["app".to_string(), "My App".to_string()]
+        );
+    }
+
+    #[test]
+    fn expand_field_codes_unescapes_literal_percent() {
+        let ctx = ExecContext {
+            icon: None,
+            name: "App",
+        };
+        let tokens = vec!["100%%".to_string()];
+        assert_eq!(expand_field_codes(&tokens, &ctx), vec!["100%".to_string()]);
+    }
+
+    #[test]
+    fn build_argv_wraps_in_terminal_when_requested() {
+        let ctx = ExecContext {
+            icon: None,
+            name: "App",
+        };
+        assert_eq!(
+            build_argv("htop", &ctx, true, Some("myterm")),
+            vec!["myterm".to_string(), "-e".to_string(), "htop".to_string()]
+        );
+    }
+
+    #[test]
+    fn build_argv_does_not_wrap_by_default() {
+        let ctx = ExecContext {
+            icon: None,
+            name: "App",
+        };
+        assert_eq!(
+            build_argv("firefox", &ctx, false, None),
+            vec!["firefox".to_string()]
+        );
+    }
+}
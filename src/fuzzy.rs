@@ -0,0 +1,94 @@
+//! Fuzzy subsequence matching for the search box.
+//!
+//! Replaces the plain `contains` substring test with a scored match: bonus
+//! points for hits at word boundaries and for consecutive-character runs,
+//! a penalty for gaps between matched characters. Sorting by descending
+//! score puts the best match first, pre-highlighted.
+
+/// Scores `candidate` as a case-insensitive fuzzy subsequence match against
+/// `query`. Returns `None` if `query`'s characters don't all appear in
+/// `candidate`, in order. An empty `query` matches everything with score 0.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let query: Vec<char> = query.to_lowercase().chars().collect();
+    let lower: Vec<char> = candidate.to_lowercase().chars().collect();
+    let original: Vec<char> = candidate.chars().collect();
+
+    let mut score: i64 = 0;
+    let mut qi = 0;
+    let mut last_match: Option<usize> = None;
+
+    for (ci, &c) in lower.iter().enumerate() {
+        if qi >= query.len() {
+            break;
+        }
+        if c != query[qi] {
+            continue;
+        }
+
+        let at_word_boundary = ci == 0
+            || !original[ci - 1].is_alphanumeric()
+            || (original[ci - 1].is_lowercase() && original[ci].is_uppercase());
+        if at_word_boundary {
+            score += 10;
+        }
+
+        match last_match {
+            Some(prev) if prev + 1 == ci => score += 5,
+            Some(prev) => score -= (ci - prev) as i64,
+            None => {}
+        }
+
+        last_match = Some(ci);
+        qi += 1;
+    }
+
+    (qi == query.len()).then_some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_query_matches_everything_with_zero_score() {
+        assert_eq!(fuzzy_score("", "Firefox"), Some(0));
+    }
+
+    #[test]
+    fn non_subsequence_does_not_match() {
+        assert_eq!(fuzzy_score("xyz", "Firefox"), None);
+    }
+
+    #[test]
+    fn matching_is_case_insensitive() {
+        assert_eq!(fuzzy_score("FIR", "firefox"), fuzzy_score("fir", "Firefox"));
+    }
+
+    #[test]
+    fn consecutive_prefix_match_scores_higher_than_scattered() {
+        let prefix = fuzzy_score("fir", "Firefox").unwrap();
+        let scattered = fuzzy_score("fex", "Firefox").unwrap();
+        assert!(prefix > scattered);
+        assert_eq!(prefix, 20);
+        assert_eq!(scattered, 4);
+    }
+
+    #[test]
+    fn word_boundary_after_space_is_bonused() {
+        // The 'c' in "Code" starts a word (after the space), while the 'i'
+        // and 's' in "Visual" do not, so "vc" scores higher than "is" despite
+        // both being two-character matches.
+        assert_eq!(fuzzy_score("vc", "Visual Code"), Some(13));
+        assert_eq!(fuzzy_score("is", "Visual Code"), Some(5));
+    }
+
+    #[test]
+    fn longer_consecutive_run_scores_higher() {
+        assert_eq!(fuzzy_score("fire", "Firefox"), Some(25));
+        assert_eq!(fuzzy_score("ffx", "Firefox"), Some(4));
+    }
+}
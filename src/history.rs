@@ -0,0 +1,162 @@
+//! Launch-history tracking and frecency scoring.
+//!
+//! Every successful launch is appended to `$XDG_CACHE_HOME/lancelot/history`
+//! as `<unix-timestamp>\t<command>`. `History::score` turns that log into a
+//! "frecency" number so frequently *and* recently used entries float to the
+//! top of the results, similar to how rmenu consults `lastlog`.
+
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const HOUR: i64 = 60 * 60;
+const DAY: i64 = 24 * HOUR;
+const WEEK: i64 = 7 * DAY;
+const MONTH: i64 = 30 * DAY;
+
+#[derive(Debug, Default, Clone)]
+pub struct History {
+    launches: HashMap<String, Vec<i64>>,
+}
+
+impl History {
+    /// Loads the history file, if any. Missing or unreadable history just
+    /// means every entry starts with a score of zero.
+    pub fn load() -> Self {
+        let mut launches: HashMap<String, Vec<i64>> = HashMap::new();
+        if let Ok(contents) = fs::read_to_string(history_path()) {
+            for line in contents.lines() {
+                let Some((ts, key)) = line.split_once('\t') else {
+                    continue;
+                };
+                if let Ok(ts) = ts.parse::<i64>() {
+                    launches.entry(key.to_string()).or_default().push(ts);
+                }
+            }
+        }
+        History { launches }
+    }
+
+    /// Records a successful launch of `key` (the entry's command) at the
+    /// current time, both in memory and on disk.
+    pub fn record(&mut self, key: &str) {
+        let now = now();
+        self.launches.entry(key.to_string()).or_default().push(now);
+
+        let path = history_path();
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+            let _ = writeln!(file, "{now}\t{key}");
+        }
+    }
+
+    /// Number of times `key` has been launched.
+    pub fn count(&self, key: &str) -> usize {
+        self.launches.get(key).map(Vec::len).unwrap_or(0)
+    }
+
+    /// Frecency score for `key`: the sum of a recency weight per launch.
+    pub fn score(&self, key: &str) -> f64 {
+        let Some(timestamps) = self.launches.get(key) else {
+            return 0.0;
+        };
+        let now = now();
+        timestamps
+            .iter()
+            .map(|&ts| {
+                let age = now - ts;
+                if age < HOUR {
+                    4.0
+                } else if age < DAY {
+                    2.0
+                } else if age < WEEK {
+                    1.0
+                } else if age < MONTH {
+                    0.5
+                } else {
+                    0.25
+                }
+            })
+            .sum()
+    }
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn history_path() -> PathBuf {
+    let cache_dir = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".cache")))
+        .unwrap_or_else(|| PathBuf::from(".cache"));
+    cache_dir.join("lancelot").join("history")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history_with(key: &str, ages: &[i64]) -> History {
+        let now = now();
+        let mut launches = HashMap::new();
+        launches.insert(key.to_string(), ages.iter().map(|age| now - age).collect());
+        History { launches }
+    }
+
+    #[test]
+    fn score_within_the_hour_is_4() {
+        let history = history_with("firefox", &[60]);
+        assert_eq!(history.score("firefox"), 4.0);
+    }
+
+    #[test]
+    fn score_within_the_day_is_2() {
+        let history = history_with("firefox", &[2 * HOUR]);
+        assert_eq!(history.score("firefox"), 2.0);
+    }
+
+    #[test]
+    fn score_within_the_week_is_1() {
+        let history = history_with("firefox", &[2 * DAY]);
+        assert_eq!(history.score("firefox"), 1.0);
+    }
+
+    #[test]
+    fn score_within_the_month_is_half() {
+        let history = history_with("firefox", &[2 * WEEK]);
+        assert_eq!(history.score("firefox"), 0.5);
+    }
+
+    #[test]
+    fn score_older_than_a_month_is_quarter() {
+        let history = history_with("firefox", &[2 * MONTH]);
+        assert_eq!(history.score("firefox"), 0.25);
+    }
+
+    #[test]
+    fn score_sums_across_launches() {
+        let history = history_with("firefox", &[60, 2 * HOUR, 2 * MONTH]);
+        assert_eq!(history.score("firefox"), 4.0 + 2.0 + 0.25);
+    }
+
+    #[test]
+    fn score_for_unknown_key_is_zero() {
+        let history = History::default();
+        assert_eq!(history.score("unknown"), 0.0);
+    }
+
+    #[test]
+    fn count_reflects_number_of_launches() {
+        let history = history_with("firefox", &[60, 2 * HOUR]);
+        assert_eq!(history.count("firefox"), 2);
+        assert_eq!(history.count("unknown"), 0);
+    }
+}
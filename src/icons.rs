@@ -0,0 +1,220 @@
+//! Icon-theme resolution.
+//!
+//! Replaces the old hardcoded `icon_loader_hicolor()` 64px PNG-only lookup
+//! with a proper freedesktop icon-theme search: the user's configured theme
+//! with its `Inherits=` fallback chain (falling back to `hicolor` if that
+//! chain doesn't already reach it), every `$XDG_DATA_DIRS/icons` plus
+//! `~/.local/share/icons`, the closest-size directory for the requested
+//! pixel size, and both raster and SVG icons. Resolved `name -> path`
+//! mappings are persisted to disk so later startups skip the directory walk.
+
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+pub struct IconResolver {
+    theme: String,
+    search_roots: Vec<PathBuf>,
+    cache: HashMap<String, PathBuf>,
+    cache_path: PathBuf,
+}
+
+impl Default for IconResolver {
+    fn default() -> Self {
+        IconResolver::new("hicolor")
+    }
+}
+
+impl IconResolver {
+    pub fn new(theme: impl Into<String>) -> Self {
+        let cache_path = icon_cache_path();
+        let cache = load_cache(&cache_path);
+        IconResolver {
+            theme: theme.into(),
+            search_roots: icon_theme_roots(),
+            cache,
+            cache_path,
+        }
+    }
+
+    /// Resolves `icon_name` to a file on disk. Absolute paths (as `Icon=`
+    /// may contain directly) are accepted verbatim.
+    pub fn resolve(&mut self, icon_name: &str, size: u16) -> Option<PathBuf> {
+        if icon_name.starts_with('/') {
+            let path = PathBuf::from(icon_name);
+            return path.is_file().then_some(path);
+        }
+
+        let cache_key = format!("{icon_name}:{size}");
+        if let Some(path) = self.cache.get(&cache_key) {
+            if path.is_file() {
+                return Some(path.clone());
+            }
+        }
+
+        let mut chain = vec![self.theme.clone()];
+        chain.extend(theme_inherits(&self.search_roots, &self.theme));
+        if !chain.iter().any(|t| t == "hicolor") {
+            chain.push("hicolor".to_string());
+        }
+
+        for theme in &chain {
+            if let Some(path) = find_in_theme(&self.search_roots, theme, icon_name, size) {
+                self.cache.insert(cache_key.clone(), path.clone());
+                append_cache_entry(&self.cache_path, &cache_key, &path);
+                return Some(path);
+            }
+        }
+        None
+    }
+}
+
+fn find_in_theme(roots: &[PathBuf], theme: &str, icon_name: &str, size: u16) -> Option<PathBuf> {
+    let mut best: Option<(u32, PathBuf)> = None;
+    for root in roots {
+        let theme_dir = root.join(theme);
+        if !theme_dir.is_dir() {
+            continue;
+        }
+        for sized_dir in size_dirs(&theme_dir) {
+            for ext in ["svg", "png", "xpm"] {
+                let candidate = sized_dir.dir.join(format!("{icon_name}.{ext}"));
+                if !candidate.is_file() {
+                    continue;
+                }
+                let distance = (sized_dir.size as i32 - size as i32).unsigned_abs();
+                let is_better = match &best {
+                    Some((best_distance, _)) => distance < *best_distance,
+                    None => true,
+                };
+                if is_better {
+                    best = Some((distance, candidate));
+                }
+            }
+        }
+    }
+    best.map(|(_, path)| path)
+}
+
+struct SizedDir {
+    dir: PathBuf,
+    size: u16,
+}
+
+/// Walks `theme_dir/<size>x<size>/<category>` (or `scalable/<category>`)
+/// subdirectories, the layout every icon theme uses per the spec.
+fn size_dirs(theme_dir: &Path) -> Vec<SizedDir> {
+    let mut dirs = Vec::new();
+    let Ok(entries) = fs::read_dir(theme_dir) else {
+        return dirs;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let dir_name = entry.file_name().to_string_lossy().into_owned();
+        let size = dir_name
+            .split('x')
+            .next()
+            .and_then(|s| s.parse::<u16>().ok())
+            .unwrap_or(128);
+        let Ok(categories) = fs::read_dir(&path) else {
+            continue;
+        };
+        for category in categories.flatten() {
+            if category.path().is_dir() {
+                dirs.push(SizedDir {
+                    dir: category.path(),
+                    size,
+                });
+            }
+        }
+    }
+    dirs
+}
+
+fn theme_inherits(roots: &[PathBuf], theme: &str) -> Vec<String> {
+    for root in roots {
+        let Ok(contents) = fs::read_to_string(root.join(theme).join("index.theme")) else {
+            continue;
+        };
+        for line in contents.lines() {
+            if let Some(value) = line.strip_prefix("Inherits=") {
+                return value.split(',').map(|s| s.trim().to_string()).collect();
+            }
+        }
+    }
+    Vec::new()
+}
+
+fn icon_theme_roots() -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+    if let Some(home) = dirs::home_dir() {
+        roots.push(home.join(".local/share/icons"));
+        roots.push(home.join(".icons"));
+    }
+    let data_dirs = std::env::var("XDG_DATA_DIRS")
+        .unwrap_or_else(|_| "/usr/local/share:/usr/share".to_string());
+    for dir in data_dirs.split(':').filter(|d| !d.is_empty()) {
+        roots.push(PathBuf::from(dir).join("icons"));
+    }
+    roots
+}
+
+fn icon_cache_path() -> PathBuf {
+    let cache_dir = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".cache")))
+        .unwrap_or_else(|| PathBuf::from(".cache"));
+    cache_dir.join("lancelot").join("icon-cache")
+}
+
+fn load_cache(path: &Path) -> HashMap<String, PathBuf> {
+    let mut cache = HashMap::new();
+    if let Ok(contents) = fs::read_to_string(path) {
+        for line in contents.lines() {
+            if let Some((key, path)) = line.split_once('\t') {
+                cache.insert(key.to_string(), PathBuf::from(path));
+            }
+        }
+    }
+    cache
+}
+
+fn append_cache_entry(path: &Path, key: &str, resolved: &Path) {
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+        let _ = writeln!(file, "{key}\t{}", resolved.display());
+    }
+}
+
+/// Decodes an icon file into RGBA pixels. SVGs are rasterized to a
+/// `size`x`size` square; raster images keep their own dimensions.
+pub fn decode_to_rgba(path: &Path, size: u32) -> Option<(Vec<u8>, [usize; 2])> {
+    if path.extension().and_then(|e| e.to_str()) == Some("svg") {
+        decode_svg(path, size)
+    } else {
+        decode_raster(path)
+    }
+}
+
+fn decode_raster(path: &Path) -> Option<(Vec<u8>, [usize; 2])> {
+    let img = image::ImageReader::open(path).ok()?.decode().ok()?;
+    let size = [img.width() as usize, img.height() as usize];
+    Some((img.to_rgba8().into_raw(), size))
+}
+
+fn decode_svg(path: &Path, size: u32) -> Option<(Vec<u8>, [usize; 2])> {
+    let svg_data = fs::read(path).ok()?;
+    let tree = usvg::Tree::from_data(&svg_data, &usvg::Options::default()).ok()?;
+    let mut pixmap = tiny_skia::Pixmap::new(size, size)?;
+    let tree_size = tree.size();
+    let scale = size as f32 / tree_size.width().max(tree_size.height()).max(1.0);
+    let transform = tiny_skia::Transform::from_scale(scale, scale);
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
+    Some((pixmap.data().to_vec(), [size as usize, size as usize]))
+}
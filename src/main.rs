@@ -1,96 +1,95 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod config;
+mod exec;
+mod fuzzy;
+mod history;
+mod icons;
+mod sandbox;
+mod sources;
+
+use config::Config;
 use eframe::egui;
-use freedesktop_desktop_entry::{default_paths, get_languages_from_env, Iter};
-use icon_loader::icon_loader_hicolor;
+use history::History;
+use icons::IconResolver;
+use sources::{enabled_sources, AppInfo, SourceFilter};
 use std::collections::HashMap;
 use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
 
 fn main() -> eframe::Result {
     env_logger::init();
+    let config = Config::load();
     let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default().with_inner_size([400.0, 300.0]),
+        viewport: egui::ViewportBuilder::default().with_inner_size(config.window_size),
         ..Default::default()
     };
     eframe::run_native(
         "App Launcher",
         options,
-        Box::new(|_cc| Ok(Box::<MyApp>::default())),
+        Box::new(move |_cc| {
+            Ok(Box::new(MyApp {
+                history: Arc::new(Mutex::new(History::load())),
+                icon_resolver: Arc::new(Mutex::new(IconResolver::new(config.icon_theme.clone()))),
+                config,
+                ..Default::default()
+            }))
+        }),
     )
 }
 
-fn sanitize_command(command: &str) -> String {
-    // Define placeholders to remove
-    let placeholders = ["%u", "%U", "%f", "%F", "%d", "%D", "%n", "%N", "%i", "%c", "%k"];
-    let mut sanitized_command = Vec::new();
-
-    for part in command.split_whitespace() {
-        // Remove placeholder arguments but keep everything else
-        if placeholders.iter().any(|&ph| part.contains(ph)) {
-            continue;
-        }
-        sanitized_command.push(part);
-    }
-
-    sanitized_command.join(" ")
-}
-
 #[derive(Default)]
 struct MyApp {
-    selected_item: Option<String>,
+    selected_item: Option<Vec<String>>,
     items: Arc<Mutex<Vec<AppInfo>>>,
     search_query: String,
     icon_cache: Arc<Mutex<HashMap<String, egui::TextureHandle>>>,
-}
-
-#[derive(Debug, Clone)]
-struct AppInfo {
-    name: String,
-    command: String,
-    icon: Option<String>,
+    history: Arc<Mutex<History>>,
+    icon_resolver: Arc<Mutex<IconResolver>>,
+    config: Config,
+    highlighted: usize,
+    search_focused: bool,
+    source_filter: SourceFilter,
+    last_query: String,
+    last_source_filter: SourceFilter,
+    loading: Arc<AtomicBool>,
 }
 
 impl MyApp {
-    /// Loads desktop files using freedesktop_desktop_entry
-    fn load_desktop_files(&self) {
-        let locales = get_languages_from_env();
+    /// Aggregates entries from every enabled `Source` (desktop files, `$PATH`
+    /// binaries, user script plugins, ...) into `items`. A no-op if an
+    /// aggregation is already in flight, since `PathSource` walks every
+    /// `$PATH` directory and `ScriptSource` spawns an external process —
+    /// calling this every frame would pile up threads and child processes
+    /// without bound.
+    fn load_entries(&self) {
+        if self.loading.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
         let items = Arc::clone(&self.items);
+        let loading = Arc::clone(&self.loading);
+        let extra_search_dirs = self.config.expanded_search_dirs();
+        let script_plugins = self.config.expanded_script_plugins();
+        let terminal = self.config.terminal.clone();
+        let enable_path_source = self.config.enable_path_source;
         thread::spawn(move || {
             let mut apps = Vec::new();
-            for entry in Iter::new(default_paths()).entries(Some(&locales)) {
-                if let Some(app_info) = MyApp::parse_desktop_entry(&entry, &locales) {
-                    apps.push(app_info);
-                }
+            for source in enabled_sources(
+                &script_plugins,
+                extra_search_dirs,
+                terminal,
+                enable_path_source,
+            ) {
+                apps.extend(source.entries());
             }
             *items.lock().unwrap() = apps;
+            loading.store(false, Ordering::SeqCst);
         });
     }
 
-    /// Parses a freedesktop desktop entry into `AppInfo`
-    fn parse_desktop_entry(
-        entry: &freedesktop_desktop_entry::DesktopEntry,
-        locales: &[String],
-    ) -> Option<AppInfo> {
-        let name = entry.name(&locales)?;
-        let command = entry.exec()?;
-        let icon = entry.icon();
-
-        // Sanitize the command: remove placeholders like %u, %U, %f, and %F
-        let command = command
-            .split_whitespace()
-            .filter(|part| !part.starts_with('%'))
-            .collect::<Vec<&str>>()
-            .join(" ");
-
-        Some(AppInfo {
-            name: name.to_string(),
-            command: command.to_string(),
-            icon: icon.map(|i| i.to_string()),
-        })
-    }
-
     /// Loads an application icon by name as a texture for display
     fn load_icon(&self, ctx: &egui::Context, icon_name: &str) -> Option<egui::TextureHandle> {
         let mut icon_cache = self.icon_cache.lock().unwrap();
@@ -100,71 +99,147 @@ impl MyApp {
             return Some(texture.clone());
         }
 
-        // Attempt to load the icon using icon_loader_hicolor
-        if let Some(icon) = icon_loader_hicolor().load_icon(icon_name) {
-            // Retrieve the icon file path for the desired size (e.g., 64x64)
-            if let Some(path) = icon.file_for_size(64).path().to_str() {
-                // Load the image
-                if let Ok(img) = image::ImageReader::open(path)
-                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))
-                    .and_then(|r| {
-                        r.decode()
-                            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))
-                    })
-                {
-                    let size = [img.width() as usize, img.height() as usize];
-                    let pixels = match img.as_flat_samples_u8() {
-                        Some(pixels) => pixels,
-                        None => return None,
-                    };
-                    let color_image = egui::ColorImage::from_rgba_unmultiplied(size, pixels.as_slice());
-                    let texture = ctx.load_texture(icon_name, color_image, Default::default());
-                    icon_cache.insert(icon_name.to_string(), texture.clone());
-                    return Some(texture);
-                }
-            }
-        }
-
-        None
+        let icon_size = self.config.icon_size;
+        let path = self
+            .icon_resolver
+            .lock()
+            .unwrap()
+            .resolve(icon_name, icon_size as u16)?;
+        let (pixels, size) = icons::decode_to_rgba(&path, icon_size)?;
+        let color_image = egui::ColorImage::from_rgba_unmultiplied(size, &pixels);
+        let texture = ctx.load_texture(icon_name, color_image, Default::default());
+        icon_cache.insert(icon_name.to_string(), texture.clone());
+        Some(texture)
     }
 }
 
 impl eframe::App for MyApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         if self.items.lock().unwrap().is_empty() {
-            self.load_desktop_files();
+            self.load_entries();
         }
 
+        let (move_down, move_up, confirm, cancel) = ctx.input(|i| {
+            (
+                i.key_pressed(egui::Key::ArrowDown),
+                i.key_pressed(egui::Key::ArrowUp),
+                i.key_pressed(egui::Key::Enter),
+                i.key_pressed(egui::Key::Escape),
+            )
+        });
+
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.heading("Select an App to Launch");
 
-            // Search bar
+            // Search bar; takes focus on startup so typing filters immediately.
             ui.horizontal(|ui| {
                 ui.label("Search:");
-                ui.text_edit_singleline(&mut self.search_query);
+                let response = ui.text_edit_singleline(&mut self.search_query);
+                if !self.search_focused {
+                    response.request_focus();
+                    self.search_focused = true;
+                }
+            });
+
+            if cancel {
+                if self.search_query.is_empty() {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                } else {
+                    self.search_query.clear();
+                }
+            }
+
+            // Filter the result list by which source an entry came from.
+            ui.horizontal(|ui| {
+                ui.label("Show:");
+                ui.radio_value(&mut self.source_filter, SourceFilter::All, "All");
+                ui.radio_value(&mut self.source_filter, SourceFilter::Desktop, "Apps");
+                ui.radio_value(&mut self.source_filter, SourceFilter::Path, "Run");
+                ui.radio_value(&mut self.source_filter, SourceFilter::Script, "Plugins");
             });
 
             let items = self.items.lock().unwrap().clone();
-            let filtered_items: Vec<AppInfo> = items
+            let history = self.history.lock().unwrap();
+            let mut scored_items: Vec<(i64, AppInfo)> = items
                 .into_iter()
-                .filter(|item| {
-                    self.search_query.is_empty()
-                        || item
-                            .name
-                            .to_lowercase()
-                            .contains(&self.search_query.to_lowercase())
+                .filter(|item| self.source_filter.matches(&item.source))
+                .filter_map(|item| {
+                    fuzzy::fuzzy_score(&self.search_query, &item.name).map(|score| (score, item))
                 })
                 .collect();
 
+            // Best fuzzy match first; ties broken by frecency, then raw launch
+            // count, then alphabetically.
+            scored_items.sort_by(|(score_a, a), (score_b, b)| {
+                let a_key = a.argv.join(" ");
+                let b_key = b.argv.join(" ");
+                score_b
+                    .cmp(score_a)
+                    .then_with(|| {
+                        history
+                            .score(&b_key)
+                            .partial_cmp(&history.score(&a_key))
+                            .unwrap_or(std::cmp::Ordering::Equal)
+                    })
+                    .then_with(|| history.count(&b_key).cmp(&history.count(&a_key)))
+                    .then_with(|| a.name.cmp(&b.name))
+            });
+            drop(history);
+
+            let mut filtered_items: Vec<AppInfo> =
+                scored_items.into_iter().map(|(_, item)| item).collect();
+            filtered_items.truncate(self.config.result_limit);
+
+            // The list is re-sorted by fuzzy score every frame, so a
+            // `highlighted` index left over from before the query or filter
+            // changed would now point at an unrelated entry. Reset to the
+            // best match whenever either changes, before clamping.
+            if self.search_query != self.last_query || self.source_filter != self.last_source_filter
+            {
+                self.highlighted = 0;
+                self.last_query = self.search_query.clone();
+                self.last_source_filter = self.source_filter;
+            }
+
+            if filtered_items.is_empty() {
+                self.highlighted = 0;
+            } else {
+                if move_down {
+                    self.highlighted = (self.highlighted + 1).min(filtered_items.len() - 1);
+                }
+                if move_up {
+                    self.highlighted = self.highlighted.saturating_sub(1);
+                }
+                self.highlighted = self.highlighted.min(filtered_items.len() - 1);
+            }
+
+            if confirm {
+                if let Some(item) = filtered_items.get(self.highlighted) {
+                    self.selected_item = Some(item.argv.clone());
+                }
+            }
+
+            let accent = egui::Color32::from_rgb(
+                self.config.accent_color[0],
+                self.config.accent_color[1],
+                self.config.accent_color[2],
+            );
+            ui.visuals_mut().selection.bg_fill = accent;
+
             ui.separator();
             egui::ScrollArea::vertical().show(ui, |ui| {
-                for item in filtered_items {
+                for (i, item) in filtered_items.iter().enumerate() {
+                    ui.set_min_height(self.config.row_height);
                     ui.horizontal(|ui| {
                         // Display the app icon if available
                         if let Some(icon_name) = &item.icon {
                             println!("Loading icon: {}", icon_name);
                             if let Some(texture) = self.load_icon(ctx, icon_name) {
-                                ui.add(egui::Image::new(&texture).max_size(egui::vec2(24.0, 24.0))); // Adjust size if needed
+                                let icon_size = self.config.icon_size as f32;
+                                ui.add(
+                                    egui::Image::new(&texture)
+                                        .max_size(egui::vec2(icon_size, icon_size)),
+                                );
                             } else {
                                 // Fallback text if the icon can't be loaded
                                 ui.label("📄");
@@ -174,24 +249,56 @@ impl eframe::App for MyApp {
                             ui.label("📄");
                         }
 
-                        // App name as a clickable button
-                        if ui.button(&item.name).clicked() {
-                            self.selected_item = Some(item.command.clone());
+                        // App name, highlighted when it's the keyboard selection
+                        if ui
+                            .selectable_label(i == self.highlighted, &item.name)
+                            .clicked()
+                        {
+                            self.selected_item = Some(item.argv.clone());
                         }
                     });
+
+                    // Desktop Actions (e.g. "New Private Window") as secondary buttons
+                    if !item.actions.is_empty() {
+                        ui.indent(item.name.clone(), |ui| {
+                            for action in &item.actions {
+                                ui.horizontal(|ui| {
+                                    if let Some(icon_name) = &action.icon {
+                                        if let Some(texture) = self.load_icon(ctx, icon_name) {
+                                            let icon_size = self.config.icon_size as f32 * 0.75;
+                                            ui.add(
+                                                egui::Image::new(&texture)
+                                                    .max_size(egui::vec2(icon_size, icon_size)),
+                                            );
+                                        }
+                                    }
+                                    if ui.small_button(&action.name).clicked() {
+                                        self.selected_item = Some(action.argv.clone());
+                                    }
+                                });
+                            }
+                        });
+                    }
+
                     ui.separator();
                 }
             });
 
             // Launch the selected application
-            if let Some(command) = &self.selected_item {
-                // sanitize the command before launching
-                let command = sanitize_command(command);
-                ui.label(format!("Launching: {}", command));
-                if let Err(e) = Command::new(command).spawn() {
-                    ui.colored_label(egui::Color32::RED, format!("Failed to launch: {}", e));
-                } else {
-                    ui.colored_label(egui::Color32::GREEN, "Launched successfully!");
+            if let Some(argv) = &self.selected_item {
+                if let Some((program, args)) = argv.split_first() {
+                    ui.label(format!("Launching: {}", argv.join(" ")));
+                    let mut cmd = Command::new(program);
+                    cmd.args(args);
+                    if sandbox::is_sandboxed() {
+                        cmd.env_clear().envs(sandbox::sanitized_env());
+                    }
+                    if let Err(e) = cmd.spawn() {
+                        ui.colored_label(egui::Color32::RED, format!("Failed to launch: {}", e));
+                    } else {
+                        ui.colored_label(egui::Color32::GREEN, "Launched successfully!");
+                        self.history.lock().unwrap().record(&argv.join(" "));
+                    }
                 }
                 self.selected_item = None;
             }
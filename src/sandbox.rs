@@ -0,0 +1,134 @@
+//! Sandbox detection and child-environment normalization.
+//!
+//! AppImage/Flatpak/Snap bundles pollute `PATH`, `LD_LIBRARY_PATH`,
+//! `GST_PLUGIN_*` and the XDG directory lists for everything they spawn.
+//! Launched GTK/GNOME apps then pick up the bundle's copies instead of the
+//! system's and crash or misbehave. Before spawning a child we strip those
+//! bundle-injected entries back out.
+
+use std::collections::HashSet;
+use std::env;
+
+pub fn is_flatpak() -> bool {
+    env::var_os("FLATPAK_ID").is_some()
+}
+
+pub fn is_snap() -> bool {
+    env::var_os("SNAP").is_some()
+}
+
+pub fn is_appimage() -> bool {
+    env::var_os("APPDIR").is_some()
+}
+
+pub fn is_sandboxed() -> bool {
+    is_flatpak() || is_snap() || is_appimage()
+}
+
+/// Environment variables that hold `:`-separated search-path lists.
+const PATH_LIKE_VARS: &[&str] = &[
+    "PATH",
+    "LD_LIBRARY_PATH",
+    "GST_PLUGIN_PATH",
+    "GST_PLUGIN_SYSTEM_PATH",
+    "XDG_DATA_DIRS",
+    "XDG_CONFIG_DIRS",
+];
+
+/// Roots injected by the detected sandbox (the AppImage mount point, the
+/// Flatpak app prefix, or the Snap install directory).
+fn bundle_roots() -> Vec<String> {
+    [
+        env::var("APPDIR").ok(),
+        is_flatpak().then(|| "/app".to_string()),
+        env::var("SNAP").ok(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Strips bundle-injected directories from a `:`-separated path list,
+/// de-duplicating while preferring the lower-priority (later, i.e. system)
+/// copy of any entry that appears more than once.
+fn normalize_path_list(value: &str, roots: &[String]) -> Option<String> {
+    let mut seen = HashSet::new();
+    let mut kept: Vec<String> = Vec::new();
+    for entry in env::split_paths(value).rev() {
+        let entry = entry.to_string_lossy().into_owned();
+        if roots.iter().any(|root| entry.starts_with(root.as_str())) {
+            continue;
+        }
+        if seen.insert(entry.clone()) {
+            kept.push(entry);
+        }
+    }
+    kept.reverse();
+    if kept.is_empty() {
+        None
+    } else {
+        Some(kept.join(":"))
+    }
+}
+
+/// Returns the environment a spawned app should see: bundle-injected search
+/// paths removed, de-duplicated, and any variable that ends up empty dropped
+/// entirely. Outside a sandbox this is just the current environment.
+pub fn sanitized_env() -> Vec<(String, String)> {
+    let vars: Vec<(String, String)> = env::vars().collect();
+    if !is_sandboxed() {
+        return vars;
+    }
+
+    let roots = bundle_roots();
+    vars.into_iter()
+        .filter_map(|(key, value)| {
+            if PATH_LIKE_VARS.contains(&key.as_str()) {
+                normalize_path_list(&value, &roots).map(|value| (key, value))
+            } else {
+                Some((key, value))
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_path_list_strips_bundle_roots() {
+        let roots = vec!["/app".to_string()];
+        let value = "/app/bin:/usr/bin:/usr/local/bin";
+        assert_eq!(
+            normalize_path_list(value, &roots),
+            Some("/usr/bin:/usr/local/bin".to_string())
+        );
+    }
+
+    #[test]
+    fn normalize_path_list_dedups_preferring_the_later_system_copy() {
+        // When the same entry appears both before and after a stripped
+        // bundle entry, the later (system, lower-priority) copy should win
+        // so PATH keeps its original relative ordering of system dirs.
+        let roots = vec!["/app".to_string()];
+        let value = "/usr/bin:/app/bin:/usr/bin";
+        assert_eq!(normalize_path_list(value, &roots), Some("/usr/bin".to_string()));
+    }
+
+    #[test]
+    fn normalize_path_list_returns_none_when_everything_is_stripped() {
+        let roots = vec!["/app".to_string()];
+        let value = "/app/bin:/app/lib";
+        assert_eq!(normalize_path_list(value, &roots), None);
+    }
+
+    #[test]
+    fn normalize_path_list_keeps_everything_outside_sandboxes() {
+        let value = "/usr/bin:/usr/local/bin";
+        assert_eq!(
+            normalize_path_list(value, &[]),
+            Some("/usr/bin:/usr/local/bin".to_string())
+        );
+    }
+}
@@ -0,0 +1,261 @@
+//! Pluggable entry sources.
+//!
+//! Every place lancelot can find a launchable [`AppInfo`] from implements
+//! [`Source`]. `MyApp` aggregates whatever sources are enabled instead of
+//! hardcoding `freedesktop_desktop_entry` as the only provider.
+
+use crate::exec::{build_argv, ExecContext};
+use freedesktop_desktop_entry::{default_paths, get_languages_from_env, DesktopEntry, Iter};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Which [`Source`] produced an [`AppInfo`], so results can be grouped or
+/// filtered by origin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceTag {
+    Desktop,
+    Path,
+    Script(String),
+}
+
+/// Coarse source category the UI lets the user filter results by.
+/// `SourceFilter::Script` matches every `SourceTag::Script(_)` regardless of
+/// which plugin produced it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SourceFilter {
+    #[default]
+    All,
+    Desktop,
+    Path,
+    Script,
+}
+
+impl SourceFilter {
+    pub fn matches(&self, tag: &SourceTag) -> bool {
+        match self {
+            SourceFilter::All => true,
+            SourceFilter::Desktop => matches!(tag, SourceTag::Desktop),
+            SourceFilter::Path => matches!(tag, SourceTag::Path),
+            SourceFilter::Script => matches!(tag, SourceTag::Script(_)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AppInfo {
+    pub name: String,
+    pub argv: Vec<String>,
+    pub icon: Option<String>,
+    pub source: SourceTag,
+    pub actions: Vec<AppAction>,
+}
+
+/// A `[Desktop Action <id>]` entry, e.g. a browser's "New Private Window".
+#[derive(Debug, Clone)]
+pub struct AppAction {
+    pub name: String,
+    pub argv: Vec<String>,
+    pub icon: Option<String>,
+}
+
+/// A provider of launchable entries.
+pub trait Source: Send {
+    fn entries(&self) -> Vec<AppInfo>;
+}
+
+/// The original `.desktop` file loader, now just one `Source` among several.
+/// `search_paths` is the freedesktop defaults plus the user's configured
+/// extra directories; `terminal` is their configured terminal emulator
+/// override, if any.
+pub struct DesktopSource {
+    pub search_paths: Vec<PathBuf>,
+    pub terminal: Option<String>,
+}
+
+impl Source for DesktopSource {
+    fn entries(&self) -> Vec<AppInfo> {
+        let locales = get_languages_from_env();
+        let mut apps = Vec::new();
+        for entry in Iter::new(self.search_paths.clone()).entries(Some(&locales)) {
+            if let Some(app_info) = parse_desktop_entry(&entry, &locales, self.terminal.as_deref())
+            {
+                apps.push(app_info);
+            }
+        }
+        apps
+    }
+}
+
+/// Parses a freedesktop desktop entry into `AppInfo`.
+fn parse_desktop_entry(
+    entry: &DesktopEntry,
+    locales: &[String],
+    terminal: Option<&str>,
+) -> Option<AppInfo> {
+    let name = entry.name(locales)?;
+    let exec = entry.exec()?;
+    let icon = entry.icon();
+
+    let ctx = ExecContext {
+        icon,
+        name: &name,
+    };
+    let argv = build_argv(&exec, &ctx, entry.terminal(), terminal);
+
+    let actions = entry
+        .actions()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|action| parse_desktop_action(entry, &action, locales, terminal))
+        .collect();
+
+    Some(AppInfo {
+        name: name.to_string(),
+        argv,
+        icon: icon.map(|i| i.to_string()),
+        source: SourceTag::Desktop,
+        actions,
+    })
+}
+
+/// Parses a single `[Desktop Action <id>]` group into an `AppAction`.
+fn parse_desktop_action(
+    entry: &DesktopEntry,
+    action: &str,
+    locales: &[String],
+    terminal: Option<&str>,
+) -> Option<AppAction> {
+    let name = entry.action_name(action, locales)?;
+    let exec = entry.action_exec(action)?;
+    let icon = entry.action_icon(action);
+
+    let ctx = ExecContext {
+        icon,
+        name: &name,
+    };
+    let argv = build_argv(&exec, &ctx, entry.terminal(), terminal);
+
+    Some(AppAction {
+        name: name.to_string(),
+        argv,
+        icon: icon.map(|i| i.to_string()),
+    })
+}
+
+/// Scans `$PATH` for executables, à la rmenu's "run" plugin.
+pub struct PathSource;
+
+impl Source for PathSource {
+    fn entries(&self) -> Vec<AppInfo> {
+        let Some(path_var) = env::var_os("PATH") else {
+            return Vec::new();
+        };
+
+        let mut seen = std::collections::HashSet::new();
+        let mut apps = Vec::new();
+        for dir in env::split_paths(&path_var) {
+            let Ok(read_dir) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in read_dir.flatten() {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if !seen.insert(name.clone()) {
+                    continue;
+                }
+                if !is_executable(&entry.path()) {
+                    continue;
+                }
+                apps.push(AppInfo {
+                    name: name.clone(),
+                    argv: vec![name],
+                    icon: None,
+                    source: SourceTag::Path,
+                    actions: Vec::new(),
+                });
+            }
+        }
+        apps
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &std::path::Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path)
+        .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &std::path::Path) -> bool {
+    path.is_file()
+}
+
+/// Runs a user-defined plugin script and parses the newline-delimited JSON
+/// entries it emits on stdout.
+pub struct ScriptSource {
+    pub path: PathBuf,
+}
+
+impl Source for ScriptSource {
+    fn entries(&self) -> Vec<AppInfo> {
+        let Ok(output) = Command::new(&self.path).output() else {
+            return Vec::new();
+        };
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let tag = self.path.to_string_lossy().into_owned();
+        stdout
+            .lines()
+            .filter_map(|line| parse_script_entry(line, &tag))
+            .collect()
+    }
+}
+
+fn parse_script_entry(line: &str, script: &str) -> Option<AppInfo> {
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+    let value: serde_json::Value = serde_json::from_str(line).ok()?;
+    let command = value.get("command")?.as_str()?;
+    Some(AppInfo {
+        name: value.get("name")?.as_str()?.to_string(),
+        argv: crate::exec::tokenize(command),
+        icon: value
+            .get("icon")
+            .and_then(|i| i.as_str())
+            .map(|s| s.to_string()),
+        source: SourceTag::Script(script.to_string()),
+        actions: Vec::new(),
+    })
+}
+
+/// The sources lancelot aggregates entries from by default. `extra_search_dirs`
+/// are appended to the freedesktop `default_paths()` for the desktop source,
+/// `script_plugins` are the user's configured script-plugin paths, and
+/// `enable_path_source` lets users opt out of scanning `$PATH` entirely.
+pub fn enabled_sources(
+    script_plugins: &[PathBuf],
+    extra_search_dirs: Vec<PathBuf>,
+    terminal: Option<String>,
+    enable_path_source: bool,
+) -> Vec<Box<dyn Source>> {
+    let mut search_paths: Vec<PathBuf> = default_paths().collect();
+    search_paths.extend(extra_search_dirs);
+
+    let mut sources: Vec<Box<dyn Source>> = vec![Box::new(DesktopSource {
+        search_paths,
+        terminal,
+    })];
+    if enable_path_source {
+        sources.push(Box::new(PathSource));
+    }
+    for script in script_plugins {
+        sources.push(Box::new(ScriptSource {
+            path: script.clone(),
+        }));
+    }
+    sources
+}